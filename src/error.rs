@@ -3,6 +3,13 @@ use std::fmt;
 
 use instant::Duration;
 
+/// Upper bound on how many nodes of a `source()` chain this module's chain
+/// walks (`classify_by_source`, and the alternate `Display`/`Debug` cause
+/// listings) will visit. Chains are expected to be acyclic and finite, but
+/// this guards against a buggy or adversarial `E` whose chain cycles,
+/// degrading to a truncated walk instead of hanging forever.
+const MAX_SOURCE_CHAIN_DEPTH: usize = 64;
+
 /// Error is the error value in an operation's
 /// result.
 ///
@@ -16,29 +23,90 @@ pub enum Error<E> {
     /// the operation should be retried according to the backoff policy, else after
     /// the specified duration. Useful for handling ratelimits like a HTTP 429 response.
     Transient(E, Option<Duration>),
+    /// NotReady means the resource exists but isn't ready yet, e.g. a service that's
+    /// still warming up or a read that hasn't become consistent. Like `Transient`, a
+    /// `None` duration means retry according to the backoff policy, else after the
+    /// specified duration. Unlike `Transient`, this should be retried on its own
+    /// cadence without consuming the normal retry budget or counting toward
+    /// `max_elapsed_time`.
+    NotReady(E, Option<Duration>),
 }
 
+/// # Breaking change
+///
+/// This bound was tightened from `E: fmt::Display` to `E: error::Error` so
+/// that alternate formatting (`{:#}`) can walk `source()`. Any `E` that
+/// implements `Display` but not `std::error::Error` will need an `Error`
+/// impl to keep using `Error<E>`'s `Display`.
 impl<E> fmt::Display for Error<E>
 where
-    E: fmt::Display,
+    E: error::Error,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        match *self {
-            Error::Permanent(ref err) | Error::Transient(ref err, _) => err.fmt(f),
+        let err: &E = match *self {
+            Error::Permanent(ref err)
+            | Error::Transient(ref err, _)
+            | Error::NotReady(ref err, _) => err,
+        };
+
+        if f.alternate() {
+            // anyhow-style chain: outermost cause first, joined by ": ".
+            write!(f, "{}", err)?;
+            let mut cause = err.source();
+            let mut depth = 1;
+            while let Some(c) = cause {
+                if depth >= MAX_SOURCE_CHAIN_DEPTH {
+                    break;
+                }
+                write!(f, ": {}", c)?;
+                cause = c.source();
+                depth += 1;
+            }
+            Ok(())
+        } else {
+            err.fmt(f)
         }
     }
 }
 
+/// # Breaking change
+///
+/// This bound was tightened from `E: fmt::Debug` to `E: error::Error` so
+/// that alternate formatting (`{:#?}`) can walk `source()`. Any `E` that
+/// implements `Debug` but not `std::error::Error` will need an `Error`
+/// impl to keep using `Error<E>`'s `Debug`.
 impl<E> fmt::Debug for Error<E>
 where
-    E: fmt::Debug,
+    E: error::Error,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let (name, err) = match *self {
-            Error::Permanent(ref err) => ("Permanent", err as &dyn fmt::Debug),
-            Error::Transient(ref err, _) => ("Transient", err as &dyn fmt::Debug),
+        let (name, err, delay) = match *self {
+            Error::Permanent(ref err) => ("Permanent", err, None),
+            Error::Transient(ref err, delay) => ("Transient", err, delay),
+            Error::NotReady(ref err, delay) => ("NotReady", err, delay),
         };
-        f.debug_tuple(name).field(err).finish()
+
+        if f.alternate() {
+            writeln!(f, "{}", name)?;
+            writeln!(f, "Caused by:")?;
+            writeln!(f, "    0: {:?}", err)?;
+            let mut cause = err.source();
+            let mut i = 1;
+            while let Some(c) = cause {
+                if i >= MAX_SOURCE_CHAIN_DEPTH {
+                    break;
+                }
+                writeln!(f, "    {}: {:?}", i, c)?;
+                cause = c.source();
+                i += 1;
+            }
+            if let Some(d) = delay {
+                writeln!(f, "retry after: {:?}", d)?;
+            }
+            Ok(())
+        } else {
+            f.debug_tuple(name).field(err as &dyn fmt::Debug).finish()
+        }
     }
 }
 
@@ -50,12 +118,15 @@ where
         match *self {
             Error::Permanent(_) => "permanent error",
             Error::Transient(..) => "transient error",
+            Error::NotReady(..) => "not ready error",
         }
     }
 
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::Permanent(ref err) | Error::Transient(ref err, _) => err.source(),
+            Error::Permanent(ref err)
+            | Error::Transient(ref err, _)
+            | Error::NotReady(ref err, _) => err.source(),
         }
     }
 
@@ -72,3 +143,112 @@ impl<E> From<E> for Error<E> {
         Error::Transient(err, None)
     }
 }
+
+/// The verdict reached for one node of an error's `source()` chain by
+/// [`Error::classify_by_source`].
+pub enum Classification {
+    /// Treat the originating error as permanent.
+    Permanent,
+    /// Treat the originating error as transient, retried after the given
+    /// duration if present, else according to the backoff policy.
+    Transient(Option<Duration>),
+    /// Treat the originating error as not-ready-yet, retried after the given
+    /// duration if present, else according to the backoff policy, without
+    /// consuming the normal retry budget.
+    NotReady(Option<Duration>),
+}
+
+impl<E> Error<E>
+where
+    E: error::Error + 'static,
+{
+    /// Classifies `err` by walking its `source()` chain, starting at `err`
+    /// itself, and running `test` against each node in turn.
+    ///
+    /// The first node for which `test` returns `Some(classification)` decides
+    /// the outcome. If the chain is exhausted, or exceeds
+    /// `MAX_SOURCE_CHAIN_DEPTH` nodes, without a match, `err` is treated as
+    /// permanent.
+    ///
+    /// The chain is expected to be acyclic, as required by `source()`'s own
+    /// contract, but the walk is bounded regardless in case a buggy `E`
+    /// violates it.
+    pub fn classify_by_source<F>(err: E, mut test: F) -> Error<E>
+    where
+        F: FnMut(&(dyn error::Error + 'static)) -> Option<Classification>,
+    {
+        let verdict = {
+            let mut cause: Option<&(dyn error::Error + 'static)> = Some(&err);
+            let mut verdict = None;
+            let mut depth = 0;
+            while let Some(e) = cause {
+                if depth >= MAX_SOURCE_CHAIN_DEPTH {
+                    break;
+                }
+                if let Some(c) = test(e) {
+                    verdict = Some(c);
+                    break;
+                }
+                cause = e.source();
+                depth += 1;
+            }
+            verdict
+        };
+
+        match verdict.unwrap_or(Classification::Permanent) {
+            Classification::Permanent => Error::Permanent(err),
+            Classification::Transient(delay) => Error::Transient(err, delay),
+            Classification::NotReady(delay) => Error::NotReady(err, delay),
+        }
+    }
+
+    /// Classifies `err` as transient if any error in its `source()` chain
+    /// downcasts to `T`, else as permanent.
+    ///
+    /// Useful for treating a buried error (e.g. an `io::Error` "connection
+    /// reset") as transient even when it's wrapped several layers deep,
+    /// without writing the chain walk by hand at every call site.
+    pub fn transient_if_source_matches<T>(err: E) -> Error<E>
+    where
+        T: error::Error + 'static,
+    {
+        Self::classify_by_source(err, |e| {
+            if e.downcast_ref::<T>().is_some() {
+                Some(Classification::Transient(None))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct CyclicError;
+
+    impl fmt::Display for CyclicError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "cyclic error")
+        }
+    }
+
+    impl error::Error for CyclicError {
+        // Its own source, forever: the pathological chain the bound guards
+        // against.
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            Some(self)
+        }
+    }
+
+    #[test]
+    fn classify_by_source_terminates_on_a_cyclic_chain() {
+        let result = Error::classify_by_source(CyclicError, |_| None);
+        assert!(
+            matches!(result, Error::Permanent(CyclicError)),
+            "an exhausted, cyclic source() chain should fall back to Permanent"
+        );
+    }
+}
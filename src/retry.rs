@@ -0,0 +1,228 @@
+use crate::backoff::Backoff;
+use crate::error::Error;
+
+/// Runs `operation` under `backoff` as usual, except that a
+/// [`Error::Transient`] result does not immediately trigger a replay of
+/// `operation`. Instead, `probe` is polled under `probe_backoff` until it
+/// reports the service healthy again, and only then is `operation` re-run,
+/// once. If that re-run fails transiently again, the same wait (a `backoff`
+/// delay, then probing) is repeated before the next run.
+///
+/// An [`Error::NotReady`] result is handled separately: it is retried under
+/// `not_ready_backoff`, a distinct instance from `backoff`, so it never
+/// draws from the `Transient` retry budget or counts toward `backoff`'s
+/// `max_elapsed_time`, per the `NotReady` contract. It goes straight back to
+/// retrying `operation`, since the resource is known to exist and isn't a
+/// health-probe candidate.
+///
+/// This mirrors the "retry manager" pattern: a transient failure stops
+/// hammering the operation itself and switches to a cheap health check,
+/// resuming the real work only once that check passes. The returned
+/// `Result` carries a plain `E`, never an `Error<E>` tag: the caller sees the
+/// eventual success, a [`Error::Permanent`]'s inner error, the inner error
+/// of the last `Transient`/`NotReady` result once `backoff` or
+/// `not_ready_backoff` is exhausted, or the probe's last error once
+/// `probe_backoff` is exhausted.
+///
+/// # At-least-once caveat
+///
+/// Because the service may have already processed a request it reported as
+/// failed, `operation` can end up called more than once for what looks like
+/// a single logical attempt. This mode is therefore opt-in, and must not be
+/// used with non-idempotent operations.
+pub fn retry_with_health_probe<B, NRB, PB, O, E, Op, Probe>(
+    mut backoff: B,
+    mut not_ready_backoff: NRB,
+    mut probe_backoff: PB,
+    mut operation: Op,
+    mut probe: Probe,
+) -> Result<O, E>
+where
+    B: Backoff,
+    NRB: Backoff,
+    PB: Backoff,
+    Op: FnMut() -> Result<O, Error<E>>,
+    Probe: FnMut() -> Result<(), E>,
+{
+    loop {
+        match operation() {
+            Ok(v) => return Ok(v),
+            Err(Error::Permanent(e)) => return Err(e),
+            Err(Error::NotReady(e, retry_after)) => {
+                match retry_after.or_else(|| not_ready_backoff.next_backoff()) {
+                    Some(d) => std::thread::sleep(d),
+                    None => return Err(e),
+                }
+            }
+            Err(Error::Transient(e, retry_after)) => {
+                match retry_after.or_else(|| backoff.next_backoff()) {
+                    Some(d) => std::thread::sleep(d),
+                    None => return Err(e),
+                }
+                wait_until_healthy(&mut probe_backoff, &mut probe)?;
+            }
+        }
+    }
+}
+
+/// Polls `probe` under `probe_backoff` until it succeeds or the backoff is
+/// exhausted.
+fn wait_until_healthy<PB, E, Probe>(probe_backoff: &mut PB, probe: &mut Probe) -> Result<(), E>
+where
+    PB: Backoff,
+    Probe: FnMut() -> Result<(), E>,
+{
+    loop {
+        match probe() {
+            Ok(()) => return Ok(()),
+            Err(e) => match probe_backoff.next_backoff() {
+                Some(d) => std::thread::sleep(d),
+                None => return Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct CountingBackoff(Rc<Cell<u32>>);
+
+    impl CountingBackoff {
+        fn new() -> Self {
+            CountingBackoff(Rc::new(Cell::new(0)))
+        }
+
+        fn calls(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    impl Backoff for CountingBackoff {
+        fn next_backoff(&mut self) -> Option<Duration> {
+            self.0.set(self.0.get() + 1);
+            Some(Duration::from_millis(0))
+        }
+    }
+
+    #[test]
+    fn not_ready_does_not_consume_the_transient_backoff() {
+        let mut attempts = 0;
+        let operation = || {
+            attempts += 1;
+            if attempts == 1 {
+                Err(Error::NotReady("warming up", None))
+            } else {
+                Ok(42)
+            }
+        };
+
+        let transient_backoff = CountingBackoff::new();
+        let result = retry_with_health_probe(
+            transient_backoff.clone(),
+            CountingBackoff::new(),
+            CountingBackoff::new(),
+            operation,
+            || Ok(()),
+        );
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(
+            transient_backoff.calls(),
+            0,
+            "a NotReady result must not draw from the Transient backoff"
+        );
+    }
+
+    struct ExhaustedBackoff;
+
+    impl Backoff for ExhaustedBackoff {
+        fn next_backoff(&mut self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn transient_suspends_replay_until_probe_succeeds() {
+        let operation_calls = Rc::new(Cell::new(0));
+        let oc = operation_calls.clone();
+        let operation = move || {
+            oc.set(oc.get() + 1);
+            if oc.get() == 1 {
+                Err(Error::Transient("down", None))
+            } else {
+                Ok(7)
+            }
+        };
+
+        let probe_calls = Rc::new(Cell::new(0));
+        let pc = probe_calls.clone();
+        let probe = move || {
+            pc.set(pc.get() + 1);
+            if pc.get() < 3 {
+                Err("still down")
+            } else {
+                Ok(())
+            }
+        };
+
+        let result = retry_with_health_probe(
+            CountingBackoff::new(),
+            CountingBackoff::new(),
+            CountingBackoff::new(),
+            operation,
+            probe,
+        );
+
+        assert_eq!(result, Ok(7));
+        assert_eq!(
+            operation_calls.get(),
+            2,
+            "operation replays once, only after the probe reports healthy"
+        );
+        assert_eq!(probe_calls.get(), 3, "probe is polled until it succeeds");
+    }
+
+    #[test]
+    fn permanent_short_circuits_without_probing() {
+        let probe_calls = Rc::new(Cell::new(0));
+        let pc = probe_calls.clone();
+        let probe = move || {
+            pc.set(pc.get() + 1);
+            Ok(())
+        };
+
+        let result: Result<i32, &str> = retry_with_health_probe(
+            CountingBackoff::new(),
+            CountingBackoff::new(),
+            CountingBackoff::new(),
+            || Err(Error::Permanent("fatal")),
+            probe,
+        );
+
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(
+            probe_calls.get(),
+            0,
+            "a Permanent result must never trigger the health probe"
+        );
+    }
+
+    #[test]
+    fn exhausted_probe_backoff_surfaces_the_probes_error() {
+        let result: Result<i32, &str> = retry_with_health_probe(
+            CountingBackoff::new(),
+            CountingBackoff::new(),
+            ExhaustedBackoff,
+            || Err(Error::Transient("down", None)),
+            || Err("still down"),
+        );
+
+        assert_eq!(result, Err("still down"));
+    }
+}
@@ -0,0 +1,204 @@
+use std::error;
+
+use instant::Duration;
+
+use crate::error::Error;
+
+/// The verdict a [`RetryableError`] reaches about itself.
+pub enum Retryability {
+    /// The error is permanent; retrying won't help.
+    Permanent,
+    /// The error is transient. `None` means retry according to the backoff
+    /// policy, `Some(duration)` means retry after the given duration, e.g.
+    /// a `Retry-After` header.
+    Transient(Option<Duration>),
+}
+
+/// Lets an error type carry its own transient/permanent verdict.
+///
+/// Paired with [`Classified`], a `Result<T, MyErr>` where
+/// `MyErr: RetryableError` can flow through `?` inside an operation body and
+/// be classified automatically, rather than being mapped into `Error<E>` by
+/// hand at every call site. See `Classified`'s docs for the pattern, and why
+/// it — rather than a bounded `From<E> for Error<E>` — is how this crate
+/// makes that work.
+pub trait RetryableError: error::Error {
+    /// Classifies `self` as permanent or transient.
+    fn retryability(&self) -> Retryability;
+}
+
+impl<E> Error<E>
+where
+    E: RetryableError,
+{
+    /// Builds an `Error<E>` from `err`'s own [`RetryableError::retryability`],
+    /// in place of the blind `Transient` that `From<E>` assumes.
+    pub fn from_retryable(err: E) -> Error<E> {
+        match err.retryability() {
+            Retryability::Permanent => Error::Permanent(err),
+            Retryability::Transient(delay) => Error::Transient(err, delay),
+        }
+    }
+}
+
+/// A classification-aware stand-in for `Error<E>`, used as the error type of
+/// a `?`-heavy operation body.
+///
+/// `Error<E>` already has a blanket `impl<E> From<E> for Error<E>` (so that
+/// `?` works at all, defaulting to `Transient`). A second, `RetryableError`-
+/// bounded `impl<E> From<E> for Error<E>` would conflict with it (E0119) —
+/// coherence doesn't allow two `From<E>` impls for the same target type, one
+/// unconditional and one bounded. `Classified<E>` is a distinct target type,
+/// so it can carry the bounded impl instead.
+///
+/// Write the operation body in terms of `Result<T, Classified<E>>`; every
+/// `?` on a `RetryableError` then classifies itself via
+/// [`RetryableError::retryability`] with no manual mapping. Hand the result
+/// to the retry runner with `?` or `.map_err(Into::into)`, since
+/// `Classified<E>` also converts into `Error<E>`.
+///
+/// ```ignore
+/// fn operation() -> Result<Response, Classified<MyErr>> {
+///     let resp = send_request()?; // MyErr -> Classified<MyErr>, auto-classified
+///     Ok(resp)
+/// }
+/// ```
+pub struct Classified<E>(pub Error<E>);
+
+impl<E> From<E> for Classified<E>
+where
+    E: RetryableError,
+{
+    fn from(err: E) -> Classified<E> {
+        Classified(Error::from_retryable(err))
+    }
+}
+
+impl<E> From<Classified<E>> for Error<E> {
+    fn from(classified: Classified<E>) -> Error<E> {
+        classified.0
+    }
+}
+
+/// An HTTP status error minimal enough to implement [`RetryableError`],
+/// gated behind the `http` feature so this crate doesn't otherwise depend
+/// on the `http` crate.
+///
+/// Mirrors how storage and HTTP client crates commonly decide retryability
+/// from a response's status code: 429 (rate limited) and 503 (unavailable)
+/// are transient, honoring `Retry-After` when the server sent one; the rest
+/// of the 4xx range is permanent; everything else is treated as transient.
+///
+/// Requires a `[dependencies] http = { version = "...", optional = true }`
+/// entry plus the `http` feature in `Cargo.toml` to resolve; this snapshot
+/// of the crate carries no manifest at all (for any module, not just this
+/// one), so that wiring isn't included here and must land with one.
+#[cfg(feature = "http")]
+#[derive(Debug)]
+pub struct HttpStatusError {
+    /// The response status that caused the request to fail.
+    pub status: http::StatusCode,
+    /// The server's `Retry-After`, if any, converted to a `Duration`.
+    pub retry_after: Option<Duration>,
+}
+
+#[cfg(feature = "http")]
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "request failed with status {}", self.status)
+    }
+}
+
+#[cfg(feature = "http")]
+impl error::Error for HttpStatusError {}
+
+#[cfg(feature = "http")]
+impl RetryableError for HttpStatusError {
+    fn retryability(&self) -> Retryability {
+        match self.status.as_u16() {
+            429 | 503 => Retryability::Transient(self.retry_after),
+            400..=499 => Retryability::Permanent,
+            _ => Retryability::Transient(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct ToyError(bool);
+
+    impl std::fmt::Display for ToyError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "toy error")
+        }
+    }
+
+    impl error::Error for ToyError {}
+
+    impl RetryableError for ToyError {
+        fn retryability(&self) -> Retryability {
+            if self.0 {
+                Retryability::Transient(None)
+            } else {
+                Retryability::Permanent
+            }
+        }
+    }
+
+    fn might_fail(fail: Option<ToyError>) -> Result<(), ToyError> {
+        match fail {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn operation(fail: Option<ToyError>) -> Result<(), Classified<ToyError>> {
+        might_fail(fail)?;
+        Ok(())
+    }
+
+    #[test]
+    fn classified_round_trips_through_question_mark_into_error() {
+        let transient: Error<ToyError> = operation(Some(ToyError(true)))
+            .map_err(Into::into)
+            .unwrap_err();
+        assert!(matches!(transient, Error::Transient(ToyError(true), None)));
+
+        let permanent: Error<ToyError> = operation(Some(ToyError(false)))
+            .map_err(Into::into)
+            .unwrap_err();
+        assert!(matches!(permanent, Error::Permanent(ToyError(false))));
+
+        assert!(operation(None).is_ok());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_status_error_classifies_common_codes() {
+        let too_many = HttpStatusError {
+            status: http::StatusCode::TOO_MANY_REQUESTS,
+            retry_after: Some(Duration::from_secs(5)),
+        };
+        assert!(
+            matches!(too_many.retryability(), Retryability::Transient(Some(d)) if d == Duration::from_secs(5))
+        );
+
+        let not_found = HttpStatusError {
+            status: http::StatusCode::NOT_FOUND,
+            retry_after: None,
+        };
+        assert!(matches!(not_found.retryability(), Retryability::Permanent));
+
+        let server_error = HttpStatusError {
+            status: http::StatusCode::INTERNAL_SERVER_ERROR,
+            retry_after: None,
+        };
+        assert!(matches!(
+            server_error.retryability(),
+            Retryability::Transient(None)
+        ));
+    }
+}